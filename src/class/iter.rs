@@ -5,7 +5,10 @@
 use crate::callback::IntoPyCallbackOutput;
 use crate::derive_utils::TryFromPyCell;
 use crate::err::PyResult;
-use crate::{ffi, IntoPy, IntoPyPointer, PyClass, PyObject, Python};
+use crate::{
+    ffi, pyclass, pyproto, IntoPy, IntoPyPointer, Py, PyAny, PyClass, PyObject, PyRef, PyRefMut,
+    Python,
+};
 
 /// Python Iterator Interface.
 ///
@@ -59,6 +62,15 @@ pub trait PyIterProtocol<'p>: PyClass {
     {
         unimplemented!()
     }
+
+    /// Advertises the (approximate) number of items left in the iteration to
+    /// `operator.length_hint`, so that consumers such as `list(iter)` can preallocate.
+    fn __length_hint__(slf: Self::Receiver) -> Self::Result
+    where
+        Self: PyIterLenHintProtocol<'p>,
+    {
+        unimplemented!()
+    }
 }
 
 pub trait PyIterIterProtocol<'p>: PyIterProtocol<'p> {
@@ -71,6 +83,11 @@ pub trait PyIterNextProtocol<'p>: PyIterProtocol<'p> {
     type Result: IntoPyCallbackOutput<PyIterNextOutput>;
 }
 
+pub trait PyIterLenHintProtocol<'p>: PyIterProtocol<'p> {
+    type Receiver: TryFromPyCell<'p, Self>;
+    type Result: IntoPyCallbackOutput<PyObject>;
+}
+
 /// Extension trait for proc-macro backend.
 #[doc(hidden)]
 pub trait PyIterSlots {
@@ -96,6 +113,26 @@ pub trait PyIterSlots {
 
 impl<'p, T> PyIterSlots for T where T: PyIterProtocol<'p> {}
 
+/// Extension trait for proc-macro backend.
+///
+/// `__length_hint__` has no dedicated `PyTypeObject` slot, so it is wired up via the type's
+/// method table instead, the same way [`PyGeneratorMethods`](trait.PyGeneratorMethods.html)
+/// wires up `send`/`throw`/`close`.
+#[doc(hidden)]
+pub trait PyIterLenHintMethods {
+    fn get_length_hint() -> ffi::PyMethodDef
+    where
+        Self: for<'p> PyIterLenHintProtocol<'p>,
+    {
+        ffi::PyMethodDef::cfunction(
+            "__length_hint__\0",
+            py_unarys_func!(PyIterLenHintProtocol, Self::__length_hint__),
+        )
+    }
+}
+
+impl<'p, T> PyIterLenHintMethods for T where T: PyIterProtocol<'p> {}
+
 /// Output of `__next__` which can either `yield` the next value in the iteration, or
 /// `return` a value to raise `StopIteration` in Python.
 ///
@@ -140,3 +177,445 @@ where
         }
     }
 }
+
+/// Python Asynchronous Iterator Interface.
+///
+/// Check [CPython doc](https://docs.python.org/3/c-api/typeobj.html#c.PyAsyncMethods.am_anext)
+/// for more.
+///
+/// # Example
+/// The following example shows how to implement a simple Python async iterator in Rust which
+/// yields the integers 1 to 5, before raising `StopAsyncIteration`.
+///
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pyo3::PyAsyncIterProtocol;
+/// use pyo3::class::iter::IterANextOutput;
+///
+/// #[pyclass]
+/// struct AsyncIter {
+///     count: usize
+/// }
+///
+/// #[pyproto]
+/// impl PyAsyncIterProtocol for AsyncIter {
+///     fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+///         slf
+///     }
+///
+///     fn __anext__(mut slf: PyRefMut<Self>) -> IterANextOutput<usize, &'static str> {
+///         if slf.count < 5 {
+///             slf.count += 1;
+///             IterANextOutput::Yield(slf.count)
+///         } else {
+///             IterANextOutput::Return("Ended")
+///         }
+///     }
+/// }
+///
+/// # let gil = Python::acquire_gil();
+/// # let py = gil.python();
+/// # let inst = Py::new(py, AsyncIter { count: 0 }).unwrap();
+/// # pyo3::py_run!(py, inst, r#"
+/// # import asyncio
+/// # async def drive():
+/// #     return [x async for x in inst]
+/// # assert asyncio.get_event_loop().run_until_complete(drive()) == [1, 2, 3, 4, 5]
+/// # "#);
+/// ```
+#[allow(unused_variables)]
+pub trait PyAsyncIterProtocol<'p>: PyClass {
+    fn __aiter__(slf: Self::Receiver) -> Self::Result
+    where
+        Self: PyAsyncIterIterProtocol<'p>,
+    {
+        unimplemented!()
+    }
+
+    fn __anext__(slf: Self::Receiver) -> Self::Result
+    where
+        Self: PyAsyncIterANextProtocol<'p>,
+    {
+        unimplemented!()
+    }
+}
+
+pub trait PyAsyncIterIterProtocol<'p>: PyAsyncIterProtocol<'p> {
+    type Receiver: TryFromPyCell<'p, Self>;
+    type Result: IntoPyCallbackOutput<PyObject>;
+}
+
+pub trait PyAsyncIterANextProtocol<'p>: PyAsyncIterProtocol<'p> {
+    type Receiver: TryFromPyCell<'p, Self>;
+    type Result: IntoPyCallbackOutput<PyIterANextOutput>;
+}
+
+/// Extension trait for proc-macro backend.
+#[doc(hidden)]
+pub trait PyAsyncIterSlots {
+    fn get_aiter() -> ffi::PyType_Slot
+    where
+        Self: for<'p> PyAsyncIterIterProtocol<'p>,
+    {
+        ffi::PyType_Slot {
+            slot: ffi::Py_am_aiter,
+            pfunc: py_unarys_func!(PyAsyncIterIterProtocol, Self::__aiter__) as _,
+        }
+    }
+    fn get_anext() -> ffi::PyType_Slot
+    where
+        Self: for<'p> PyAsyncIterANextProtocol<'p>,
+    {
+        ffi::PyType_Slot {
+            slot: ffi::Py_am_anext,
+            pfunc: py_unarys_func!(PyAsyncIterANextProtocol, Self::__anext__) as _,
+        }
+    }
+}
+
+impl<'p, T> PyAsyncIterSlots for T where T: PyAsyncIterProtocol<'p> {}
+
+/// Python Awaitable Interface.
+///
+/// Only the `__await__` half is needed here, to make [`PyReadyValue`](struct.PyReadyValue.html)
+/// satisfy `await`. Check [CPython doc](https://docs.python.org/3/c-api/typeobj.html#c.PyAsyncMethods.am_await)
+/// for more.
+#[allow(unused_variables)]
+pub trait PyAwaitProtocol<'p>: PyClass {
+    fn __await__(slf: Self::Receiver) -> Self::Result
+    where
+        Self: PyAwaitAwaitProtocol<'p>,
+    {
+        unimplemented!()
+    }
+}
+
+pub trait PyAwaitAwaitProtocol<'p>: PyAwaitProtocol<'p> {
+    type Receiver: TryFromPyCell<'p, Self>;
+    type Result: IntoPyCallbackOutput<PyObject>;
+}
+
+/// Extension trait for proc-macro backend.
+#[doc(hidden)]
+pub trait PyAwaitSlots {
+    fn get_await() -> ffi::PyType_Slot
+    where
+        Self: for<'p> PyAwaitAwaitProtocol<'p>,
+    {
+        ffi::PyType_Slot {
+            slot: ffi::Py_am_await,
+            pfunc: py_unarys_func!(PyAwaitAwaitProtocol, Self::__await__) as _,
+        }
+    }
+}
+
+impl<'p, T> PyAwaitSlots for T where T: PyAwaitProtocol<'p> {}
+
+/// A one-shot awaitable wrapping an already-computed value.
+///
+/// `am_anext` must return something `async for` can `await`, not a bare value, so
+/// [`IterANextOutput::Yield`](enum.IterANextOutput.html) values are wrapped in one of these
+/// before being handed back to Python: `__await__` returns the object itself (wired through
+/// `Py_am_await` via [`PyAwaitProtocol`](trait.PyAwaitProtocol.html), the same way `__iter__`
+/// and `__next__` go through `PyIterProtocol` rather than a plain `#[pymethods]` block), and its
+/// single `__next__` call immediately raises `StopIteration(value)`, which is exactly how
+/// CPython's `await`/`yield from` machinery unwraps an already-resolved result without
+/// suspending.
+#[pyclass]
+struct PyReadyValue {
+    value: Option<PyObject>,
+}
+
+#[pyproto]
+impl PyAwaitProtocol for PyReadyValue {
+    fn __await__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+}
+
+#[pyproto]
+impl PyIterProtocol for PyReadyValue {
+    fn __next__(mut slf: PyRefMut<Self>) -> PyResult<PyObject> {
+        let value = slf.value.take().unwrap_or_else(|| slf.py().None());
+        Err(crate::exceptions::PyStopIteration::new_err((value,)))
+    }
+}
+
+/// Output of `__anext__` which can either `yield` the next value in the iteration, or
+/// `return` a value to raise `StopAsyncIteration` in Python.
+///
+/// See [`PyAsyncIterProtocol`](trait.PyAsyncIterProtocol.html) for an example.
+pub enum IterANextOutput<T, U> {
+    Yield(T),
+    Return(U),
+}
+
+pub type PyIterANextOutput = IterANextOutput<PyObject, PyObject>;
+
+impl IntoPyCallbackOutput<*mut ffi::PyObject> for PyIterANextOutput {
+    fn convert(self, py: Python) -> PyResult<*mut ffi::PyObject> {
+        match self {
+            IterANextOutput::Yield(o) => {
+                Ok(Py::new(py, PyReadyValue { value: Some(o) })?.into_ptr())
+            }
+            IterANextOutput::Return(opt) => {
+                Err(crate::exceptions::PyStopAsyncIteration::new_err((opt,)))
+            }
+        }
+    }
+}
+
+impl<T, U> IntoPyCallbackOutput<PyIterANextOutput> for IterANextOutput<T, U>
+where
+    T: IntoPy<PyObject>,
+    U: IntoPy<PyObject>,
+{
+    fn convert(self, py: Python) -> PyResult<PyIterANextOutput> {
+        match self {
+            IterANextOutput::Yield(o) => Ok(IterANextOutput::Yield(o.into_py(py))),
+            IterANextOutput::Return(o) => Ok(IterANextOutput::Return(o.into_py(py))),
+        }
+    }
+}
+
+impl<T> IntoPyCallbackOutput<PyIterANextOutput> for Option<T>
+where
+    T: IntoPy<PyObject>,
+{
+    fn convert(self, py: Python) -> PyResult<PyIterANextOutput> {
+        match self {
+            Some(o) => Ok(PyIterANextOutput::Yield(o.into_py(py))),
+            None => Ok(PyIterANextOutput::Return(py.None())),
+        }
+    }
+}
+
+/// Python Generator Interface.
+///
+/// `send`, `throw` and `close` are plain methods on a Python generator object rather than
+/// `PyTypeObject` slots, so unlike [`PyIterProtocol`](trait.PyIterProtocol.html) this protocol
+/// is wired up via the type's method table instead of `tp_iter`/`tp_iternext`.
+///
+/// # Example
+/// The following example shows how to implement a simple Rust generator which echoes back
+/// whatever value is sent in, until `None` is sent, at which point it returns `"done"`.
+///
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pyo3::PyGeneratorProtocol;
+/// use pyo3::class::iter::IterNextOutput;
+///
+/// #[pyclass]
+/// struct Echo {
+///     last: Option<PyObject>,
+/// }
+///
+/// #[pyproto]
+/// impl PyGeneratorProtocol for Echo {
+///     fn __send__(mut slf: PyRefMut<Self>, value: Option<PyObject>) -> IterNextOutput<Option<PyObject>, &'static str> {
+///         match value {
+///             Some(value) => IterNextOutput::Yield(Some(value)),
+///             None => IterNextOutput::Return("done"),
+///         }
+///     }
+/// }
+/// ```
+#[allow(unused_variables)]
+pub trait PyGeneratorProtocol<'p>: PyClass {
+    fn __send__(slf: Self::Receiver, value: Self::Value) -> Self::Result
+    where
+        Self: PyGeneratorSendProtocol<'p>,
+    {
+        unimplemented!()
+    }
+
+    fn __throw__(slf: Self::Receiver, exc: Self::Exc) -> Self::Result
+    where
+        Self: PyGeneratorThrowProtocol<'p>,
+    {
+        unimplemented!()
+    }
+
+    fn __close__(slf: Self::Receiver) -> Self::Result
+    where
+        Self: PyGeneratorCloseProtocol<'p>,
+    {
+        unimplemented!()
+    }
+}
+
+pub trait PyGeneratorSendProtocol<'p>: PyGeneratorProtocol<'p> {
+    type Receiver: TryFromPyCell<'p, Self>;
+    type Value: crate::FromPyObject<'p>;
+    type Result: IntoPyCallbackOutput<PyIterNextOutput>;
+}
+
+pub trait PyGeneratorThrowProtocol<'p>: PyGeneratorProtocol<'p> {
+    type Receiver: TryFromPyCell<'p, Self>;
+    type Exc: crate::FromPyObject<'p>;
+    type Result: IntoPyCallbackOutput<PyIterNextOutput>;
+}
+
+pub trait PyGeneratorCloseProtocol<'p>: PyGeneratorProtocol<'p> {
+    type Receiver: TryFromPyCell<'p, Self>;
+    type Result: IntoPyCallbackOutput<PyObject>;
+}
+
+/// Extension trait for proc-macro backend.
+#[doc(hidden)]
+pub trait PyGeneratorMethods {
+    /// `send` takes a single positional argument and no keywords, the same as CPython's own
+    /// `generator.send`, so it is registered `METH_O`, matching the 2-arg `(self, value)` shape
+    /// `py_binarys_func!` generates (there is no existing `PyMethodDef` constructor for this
+    /// calling convention in this file, so the struct is built directly, the same way
+    /// `PyType_Slot`s are above).
+    fn get_send() -> ffi::PyMethodDef
+    where
+        Self: for<'p> PyGeneratorSendProtocol<'p>,
+    {
+        ffi::PyMethodDef {
+            ml_name: "send\0".as_ptr() as *const std::os::raw::c_char,
+            ml_meth: py_binarys_func!(PyGeneratorSendProtocol, Self::__send__),
+            ml_flags: ffi::METH_O,
+            ml_doc: std::ptr::null(),
+        }
+    }
+    /// See [`get_send`](#method.get_send): `throw` is likewise `METH_O`. Unlike CPython's
+    /// `generator.throw`, this only accepts the single-argument `throw(exc_instance)` form;
+    /// the legacy 3-argument `throw(type, value, traceback)` form is not `METH_O` and will
+    /// raise a `TypeError` for its extra arguments.
+    fn get_throw() -> ffi::PyMethodDef
+    where
+        Self: for<'p> PyGeneratorThrowProtocol<'p>,
+    {
+        ffi::PyMethodDef {
+            ml_name: "throw\0".as_ptr() as *const std::os::raw::c_char,
+            ml_meth: py_binarys_func!(PyGeneratorThrowProtocol, Self::__throw__),
+            ml_flags: ffi::METH_O,
+            ml_doc: std::ptr::null(),
+        }
+    }
+    fn get_close() -> ffi::PyMethodDef
+    where
+        Self: for<'p> PyGeneratorCloseProtocol<'p>,
+    {
+        ffi::PyMethodDef::cfunction(
+            "close\0",
+            py_unarys_func!(PyGeneratorCloseProtocol, Self::__close__),
+        )
+    }
+}
+
+impl<'p, T> PyGeneratorMethods for T where T: PyGeneratorProtocol<'p> {}
+
+/// A ready-made `#[pyclass]` that exposes any Rust [`Iterator`](std::iter::Iterator) as a Python
+/// iterator, so that returning a lazy sequence from Rust doesn't require hand-writing a
+/// [`PyIterProtocol`](trait.PyIterProtocol.html) implementation.
+///
+/// `None` returned by the wrapped iterator's `next()` is turned into `StopIteration` via the
+/// `Option<T>: IntoPyCallbackOutput<PyIterNextOutput>` impl above.
+///
+/// # Example
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pyo3::class::iter::PyRustIterator;
+///
+/// #[pyfunction]
+/// fn count_to(py: Python, n: usize) -> PyResult<Py<PyRustIterator>> {
+///     PyRustIterator::from_iter(py, 1..=n)
+/// }
+/// ```
+#[pyclass]
+pub struct PyRustIterator {
+    iter: Box<dyn Iterator<Item = PyObject> + Send>,
+}
+
+impl PyRustIterator {
+    /// Wraps any `T: Iterator` whose items convert `IntoPy<PyObject>` into a `#[pyclass]`
+    /// instance implementing `__iter__`/`__next__`.
+    pub fn from_iter<T>(py: Python, iter: T) -> PyResult<Py<Self>>
+    where
+        T: Iterator + Send + 'static,
+        T::Item: IntoPy<PyObject>,
+    {
+        Py::new(py, PyRustIterator { iter: Box::new(IntoPyIter(iter)) })
+    }
+}
+
+/// Adapts an `Iterator<Item = I>` into an `Iterator<Item = PyObject>` by converting each item
+/// with [`IntoPy`] as it is pulled, rather than eagerly up front.
+struct IntoPyIter<T>(T);
+
+impl<T> Iterator for IntoPyIter<T>
+where
+    T: Iterator,
+    T::Item: IntoPy<PyObject>,
+{
+    type Item = PyObject;
+
+    fn next(&mut self) -> Option<PyObject> {
+        self.0
+            .next()
+            .map(|item| Python::with_gil(|py| item.into_py(py)))
+    }
+}
+
+#[pyproto]
+impl PyIterProtocol for PyRustIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> Option<PyObject> {
+        slf.iter.next()
+    }
+}
+
+/// Drives a Python iterator one step, the same way `__next__` is driven from the Python side,
+/// but round-trips the `return` value a `yield from` expression would see.
+///
+/// Calls `next(iter)`: if it yields a value, returns `IterNextOutput::Yield(value)`; if it raises
+/// `StopIteration`, catches it, reads the exception's `.value` attribute and returns
+/// `IterNextOutput::Return(value)` instead of propagating the exception. Any other raised
+/// exception, including a failure to read `.value` off a caught `StopIteration`, is propagated
+/// as-is.
+///
+/// This is the Rust-driving-a-Python-iterator counterpart to
+/// [`crate::types::PyIterator`](../types/struct.PyIterator.html), which only exposes the plain
+/// `Iterator<Item = PyResult<&PyAny>>` view and has no way to observe a `return` value.
+///
+/// # Example
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pyo3::class::iter::{next_with_return, IterNextOutput};
+/// use pyo3::types::PyDict;
+///
+/// # let gil = Python::acquire_gil();
+/// # let py = gil.python();
+/// let globals = PyDict::new(py);
+/// py.run(
+///     "def gen():\n    yield 1\n    yield 2\n    return 'done'\ng = gen()",
+///     Some(globals),
+///     None,
+/// )
+/// .unwrap();
+/// let g = globals.get_item("g").unwrap();
+///
+/// assert!(matches!(next_with_return(g).unwrap(), IterNextOutput::Yield(_)));
+/// assert!(matches!(next_with_return(g).unwrap(), IterNextOutput::Yield(_)));
+/// match next_with_return(g).unwrap() {
+///     IterNextOutput::Return(value) => assert_eq!(value.extract::<String>(py).unwrap(), "done"),
+///     IterNextOutput::Yield(_) => panic!("expected Return"),
+/// }
+/// ```
+pub fn next_with_return(iter: &PyAny) -> PyResult<PyIterNextOutput> {
+    let py = iter.py();
+    match iter.call_method0("__next__") {
+        Ok(obj) => Ok(IterNextOutput::Yield(obj.into_py(py))),
+        Err(err) if err.is_instance::<crate::exceptions::PyStopIteration>(py) => {
+            let value = err.into_value(py).getattr(py, "value")?;
+            Ok(IterNextOutput::Return(value))
+        }
+        Err(err) => Err(err),
+    }
+}